@@ -1,14 +1,62 @@
+use std::collections::BTreeMap;
 use std::error::Error;
+use std::path::PathBuf;
 
+use libp2p::Multiaddr;
 use tracing_subscriber::EnvFilter;
 use clap::Parser;
-use utils::peer_id::{generate_new_keypair_and_peer_id, generate_peer_id, DefaultUserDirectoryProvider};
+use clap::ValueEnum;
+use bridge_nodes::utils::config::{self, NodeConfig};
+use bridge_nodes::utils::key_format::{KeyFormat, KeyType};
+use bridge_nodes::utils::mnemonic::generate_mnemonic;
+use bridge_nodes::utils::node_record::{generate_node_record, read_node_record};
+use bridge_nodes::utils::peer_id::{
+    generate_new_keypair_and_peer_id_from_mnemonic, generate_new_keypair_and_peer_id_with_encryption,
+    generate_new_keypair_and_peer_id_with_options, generate_peer_id_with_format,
+    DefaultUserDirectoryProvider,
+};
 
-mod utils;
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum KeyTypeArg {
+    Ed25519,
+    Secp256k1,
+}
+
+impl From<KeyTypeArg> for KeyType {
+    fn from(arg: KeyTypeArg) -> Self {
+        match arg {
+            KeyTypeArg::Ed25519 => KeyType::Ed25519,
+            KeyTypeArg::Secp256k1 => KeyType::Secp256k1,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum KeyFormatArg {
+    Base64,
+    Pem,
+}
+
+impl From<KeyFormatArg> for KeyFormat {
+    fn from(arg: KeyFormatArg) -> Self {
+        match arg {
+            KeyFormatArg::Base64 => KeyFormat::Base64Protobuf,
+            KeyFormatArg::Pem => KeyFormat::Pem,
+        }
+    }
+}
+
+fn parse_key_value(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("Expected KEY=VALUE, got `{}`", s))
+}
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
+    /// With --legacy-seed, an arbitrary seed string (SHA3-256-hashed).
+    /// Otherwise, a BIP39 mnemonic phrase to derive the keypair from.
     #[arg(short, long)]
     seed_phrase: Option<String>,
 
@@ -17,6 +65,73 @@ struct Args {
 
     #[arg(short, long)]
     read_peer_id: bool,
+
+    /// Generate and print a fresh 24-word BIP39 mnemonic, then exit. Back it
+    /// up; it is never written to disk.
+    #[arg(long)]
+    generate_mnemonic: bool,
+
+    /// Treat --seed-phrase as a raw string hashed with SHA3-256, the
+    /// pre-BIP39 behavior. Non-standard; prefer a BIP39 mnemonic.
+    #[arg(long)]
+    legacy_seed: bool,
+
+    /// Optional BIP39 passphrase (the "25th word") applied when deriving
+    /// from --seed-phrase as a mnemonic. Ignored with --legacy-seed.
+    #[arg(long)]
+    mnemonic_passphrase: Option<String>,
+
+    /// Encrypt the keypair at rest. Combine with --passphrase to supply the
+    /// passphrase non-interactively, otherwise you will be prompted.
+    #[arg(long)]
+    encrypt: bool,
+
+    /// Passphrase used to encrypt (with --new-peer-id --encrypt) or decrypt
+    /// (with --read-peer-id) the keypair file.
+    #[arg(long)]
+    passphrase: Option<String>,
+
+    /// Curve for a freshly generated keypair. Only applies when neither
+    /// --seed-phrase nor --legacy-seed is used (those remain ed25519-only).
+    #[arg(long, value_enum)]
+    key_type: Option<KeyTypeArg>,
+
+    /// On-disk encoding for the keypair file.
+    #[arg(long, value_enum)]
+    format: Option<KeyFormatArg>,
+
+    /// Print the node's signed node record (ENR-style), if one has been
+    /// generated, and exit.
+    #[arg(long)]
+    print_enr: bool,
+
+    /// Sign and persist a node record (ENR-style) for this node's listen
+    /// addresses, alongside its keypair. Requires a keypair/master seed to
+    /// already exist (see --new-peer-id).
+    #[arg(long)]
+    generate_enr: bool,
+
+    /// Multiaddr this node listens on. Repeat for multiple addresses. Used
+    /// with --generate-enr.
+    #[arg(long = "listen-addr")]
+    listen_addrs: Vec<String>,
+
+    /// Arbitrary `key=value` pair to include in the node record. Repeat for
+    /// multiple pairs. Used with --generate-enr.
+    #[arg(long = "enr-pair", value_parser = parse_key_value)]
+    enr_pairs: Vec<(String, String)>,
+
+    /// Sequence number for the generated node record. Defaults to one more
+    /// than the previous record's, or 1 if none exists yet. Used with
+    /// --generate-enr.
+    #[arg(long)]
+    enr_seq: Option<u64>,
+
+    /// Path to a `config.toml` with node identity/network/peer settings.
+    /// Defaults to the per-OS config directory if not given. CLI flags
+    /// override values loaded from the file.
+    #[arg(long)]
+    config: Option<PathBuf>,
 }
 
 
@@ -29,8 +144,134 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let args = Args::parse();
     let user_dir_provider = DefaultUserDirectoryProvider;
 
-    if args.new_peer_id && ! args.read_peer_id {
-        match generate_new_keypair_and_peer_id(args.seed_phrase.as_deref(), &user_dir_provider) {
+    let node_config: Option<NodeConfig> = match args.config.clone().or_else(|| config::default_config_path(&user_dir_provider)) {
+        Some(path) if path.exists() => Some(config::load_config(&path)?),
+        _ => None,
+    };
+
+    // Honors `[identity] keypair_dir` from the config file, if set.
+    let user_dir_provider = config::ConfiguredUserDirectoryProvider::new(
+        user_dir_provider,
+        node_config.as_ref().map(|c| &c.identity),
+    );
+
+    if args.print_enr {
+        let format: KeyFormat = args
+            .format
+            .map(Into::into)
+            .or_else(|| node_config.as_ref().map(|c| c.identity.format.into()))
+            .unwrap_or(KeyFormat::Base64Protobuf);
+        match read_node_record(format, args.passphrase.as_deref(), &user_dir_provider) {
+            Ok(record) => {
+                println!("Node record (seq {}):", record.seq());
+                println!("  peer id : {}", record.peer_id()?);
+                println!("  addrs   : {:?}", record.addrs()?);
+                println!("  pairs   : {:?}", record.pairs());
+            }
+            Err(e) => {
+                eprintln!("Error reading node record : {}", e);
+            }
+        }
+    }
+    else if args.generate_enr {
+        let format: KeyFormat = args
+            .format
+            .map(Into::into)
+            .or_else(|| node_config.as_ref().map(|c| c.identity.format.into()))
+            .unwrap_or(KeyFormat::Base64Protobuf);
+
+        let addrs: Result<Vec<Multiaddr>, Box<dyn Error>> = args
+            .listen_addrs
+            .iter()
+            .map(|addr| addr.parse().map_err(|e| format!("Invalid multiaddr {}: {}", addr, e).into()))
+            .collect();
+
+        match addrs {
+            Ok(addrs) => {
+                let seq = args.enr_seq.unwrap_or_else(|| {
+                    read_node_record(format, args.passphrase.as_deref(), &user_dir_provider)
+                        .map(|record| record.seq() + 1)
+                        .unwrap_or(1)
+                });
+                let pairs: BTreeMap<String, String> = args.enr_pairs.into_iter().collect();
+
+                match generate_node_record(addrs, seq, pairs, format, args.passphrase.as_deref(), &user_dir_provider) {
+                    Ok(record) => {
+                        println!("Generated node record (seq {}):", record.seq());
+                        match record.peer_id() {
+                            Ok(peer_id) => println!("  peer id : {}", peer_id),
+                            Err(e) => eprintln!("Error reading generated node record's peer id : {}", e),
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Error generating node record : {}", e);
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("Error : {}", e);
+            }
+        }
+    }
+    else if args.generate_mnemonic {
+        match generate_mnemonic() {
+            Ok(mnemonic) => {
+                println!("Write this down and keep it safe, it will not be shown again:");
+                println!("{}", mnemonic);
+            }
+            Err(e) => {
+                eprintln!("Error generating mnemonic : {}", e);
+            }
+        }
+    }
+    else if args.new_peer_id && ! args.read_peer_id {
+        let encrypt = args.encrypt || node_config.as_ref().map(|c| c.identity.encrypt).unwrap_or(false);
+        let passphrase = if encrypt {
+            Some(args.passphrase.clone().unwrap_or_else(|| {
+                rpassword::prompt_password("New keypair passphrase: ")
+                    .expect("Failed to read passphrase")
+            }))
+        } else {
+            None
+        };
+
+        let format: KeyFormat = args
+            .format
+            .map(Into::into)
+            .or_else(|| node_config.as_ref().map(|c| c.identity.format.into()))
+            .unwrap_or(KeyFormat::Base64Protobuf);
+
+        let result = match (args.seed_phrase.as_deref(), args.legacy_seed) {
+            (seed_phrase, true) => generate_new_keypair_and_peer_id_with_encryption(
+                seed_phrase,
+                format,
+                passphrase.as_deref(),
+                &user_dir_provider,
+            ),
+            (Some(mnemonic), false) => generate_new_keypair_and_peer_id_from_mnemonic(
+                mnemonic,
+                args.mnemonic_passphrase.as_deref(),
+                format,
+                passphrase.as_deref(),
+                &user_dir_provider,
+            ),
+            (None, false) => {
+                let key_type: KeyType = args
+                    .key_type
+                    .map(Into::into)
+                    .or_else(|| node_config.as_ref().map(|c| c.identity.key_type.into()))
+                    .unwrap_or(KeyType::Ed25519);
+                generate_new_keypair_and_peer_id_with_options(
+                    key_type,
+                    format,
+                    None,
+                    passphrase.as_deref(),
+                    &user_dir_provider,
+                )
+            }
+        };
+
+        match result {
             Ok(peer_id) => {
                 println!("Peer ID : {}", peer_id);
             }
@@ -40,7 +281,12 @@ async fn main() -> Result<(), Box<dyn Error>> {
         }
     }
     else if args.read_peer_id {
-        match generate_peer_id(&user_dir_provider) {
+        let format: KeyFormat = args
+            .format
+            .map(Into::into)
+            .or_else(|| node_config.as_ref().map(|c| c.identity.format.into()))
+            .unwrap_or(KeyFormat::Base64Protobuf);
+        match generate_peer_id_with_format(&user_dir_provider, format, args.passphrase.as_deref()) {
             Ok(peer_id) => {
                 println!("Peer ID : {}", peer_id);
             }
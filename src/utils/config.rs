@@ -0,0 +1,283 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use libp2p::Multiaddr;
+use serde::{Deserialize, Serialize};
+
+use super::key_format::{KeyFormat, KeyType};
+use super::peer_id::UserDirectoryProvider;
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// A reproducible, version-controllable node setup, loaded from a
+/// `config.toml` instead of ad-hoc CLI flags (CLI flags still override file
+/// values when both are given). Unknown fields are rejected so typos don't
+/// silently no-op.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NodeConfig {
+    pub identity: IdentityConfig,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    /// Static per-peer overrides, keyed by an operator-chosen name, i.e.
+    /// `[peer.some_name]` sections.
+    #[serde(default, rename = "peer")]
+    pub peers: BTreeMap<String, PeerOverride>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IdentityConfig {
+    /// Overrides the default per-OS directory the keypair (or master seed)
+    /// and node record are stored in when set. Applied via
+    /// [`ConfiguredUserDirectoryProvider`].
+    #[serde(default)]
+    pub keypair_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub encrypt: bool,
+    #[serde(default = "default_key_type")]
+    pub key_type: KeyTypeConfig,
+    #[serde(default = "default_key_format")]
+    pub format: KeyFormatConfig,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyTypeConfig {
+    Ed25519,
+    Secp256k1,
+}
+
+impl From<KeyTypeConfig> for KeyType {
+    fn from(config: KeyTypeConfig) -> Self {
+        match config {
+            KeyTypeConfig::Ed25519 => KeyType::Ed25519,
+            KeyTypeConfig::Secp256k1 => KeyType::Secp256k1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyFormatConfig {
+    Base64,
+    Pem,
+}
+
+impl From<KeyFormatConfig> for KeyFormat {
+    fn from(config: KeyFormatConfig) -> Self {
+        match config {
+            KeyFormatConfig::Base64 => KeyFormat::Base64Protobuf,
+            KeyFormatConfig::Pem => KeyFormat::Pem,
+        }
+    }
+}
+
+/// Wraps a [`UserDirectoryProvider`], honoring [`IdentityConfig::keypair_dir`]
+/// when set and otherwise deferring to `inner`'s default resolution.
+pub struct ConfiguredUserDirectoryProvider<T: UserDirectoryProvider> {
+    inner: T,
+    keypair_dir: Option<PathBuf>,
+}
+
+impl<T: UserDirectoryProvider> ConfiguredUserDirectoryProvider<T> {
+    pub fn new(inner: T, identity: Option<&IdentityConfig>) -> Self {
+        ConfiguredUserDirectoryProvider {
+            inner,
+            keypair_dir: identity.and_then(|identity| identity.keypair_dir.clone()),
+        }
+    }
+}
+
+impl<T: UserDirectoryProvider> UserDirectoryProvider for ConfiguredUserDirectoryProvider<T> {
+    fn get_config_dir(&self) -> Option<PathBuf> {
+        self.keypair_dir.clone().or_else(|| self.inner.get_config_dir())
+    }
+}
+
+fn default_key_type() -> KeyTypeConfig {
+    KeyTypeConfig::Ed25519
+}
+
+fn default_key_format() -> KeyFormatConfig {
+    KeyFormatConfig::Base64
+}
+
+/// Parsed and validated, but not yet consumed: no networking stack is wired
+/// up in this crate yet, so these values currently have no effect beyond
+/// round-tripping through [`load_config`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkConfig {
+    #[serde(default)]
+    pub listen_addrs: Vec<String>,
+    #[serde(default = "default_keepalive_min_secs")]
+    pub keepalive_min_secs: u64,
+    #[serde(default = "default_keepalive_max_secs")]
+    pub keepalive_max_secs: u64,
+}
+
+impl NetworkConfig {
+    pub fn listen_multiaddrs(&self) -> Result<Vec<Multiaddr>, Box<dyn Error>> {
+        self.listen_addrs
+            .iter()
+            .map(|addr| addr.parse().map_err(|e| format!("Invalid multiaddr {}: {}", addr, e).into()))
+            .collect()
+    }
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        NetworkConfig {
+            listen_addrs: Vec::new(),
+            keepalive_min_secs: default_keepalive_min_secs(),
+            keepalive_max_secs: default_keepalive_max_secs(),
+        }
+    }
+}
+
+fn default_keepalive_min_secs() -> u64 {
+    10
+}
+
+fn default_keepalive_max_secs() -> u64 {
+    60
+}
+
+/// Parsed and validated, but not yet consumed: no peer-dialing logic exists
+/// in this crate yet, so these overrides currently have no effect beyond
+/// round-tripping through [`load_config`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PeerOverride {
+    #[serde(default)]
+    pub peer_id: Option<String>,
+    #[serde(default)]
+    pub addr: Option<String>,
+    #[serde(default)]
+    pub pre_shared_secret: Option<String>,
+}
+
+/// Loads and validates a `config.toml` at `path`, rejecting unknown fields.
+pub fn load_config(path: &Path) -> Result<NodeConfig, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+
+    toml::from_str(&contents)
+        .map_err(|e| format!("Invalid config file {}: {}", path.display(), e).into())
+}
+
+/// The default `config.toml` location, alongside the keypair file.
+pub fn default_config_path<T: UserDirectoryProvider>(provider: &T) -> Option<PathBuf> {
+    provider.get_config_dir().map(|dir| dir.join(CONFIG_FILE_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn write_config(contents: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().expect("Failed to create temp config file");
+        file.write_all(contents.as_bytes()).expect("Failed to write temp config file");
+        file
+    }
+
+    #[test]
+    fn test_load_minimal_config() {
+        let file = write_config(
+            r#"
+            [identity]
+            "#,
+        );
+
+        let config = load_config(file.path()).expect("Failed to load minimal config");
+
+        assert_eq!(config.identity.key_type, KeyTypeConfig::Ed25519);
+        assert_eq!(config.identity.format, KeyFormatConfig::Base64);
+        assert!(!config.identity.encrypt);
+        assert!(config.network.listen_addrs.is_empty());
+        assert!(config.peers.is_empty());
+    }
+
+    #[test]
+    fn test_load_config_with_peer_overrides() {
+        let file = write_config(
+            r#"
+            [identity]
+            encrypt = true
+            key_type = "secp256k1"
+
+            [network]
+            listen_addrs = ["/ip4/0.0.0.0/tcp/4001"]
+
+            [peer.alice]
+            peer_id = "12D3KooWExample"
+            addr = "/ip4/203.0.113.1/tcp/4001"
+            pre_shared_secret = "shh"
+            "#,
+        );
+
+        let config = load_config(file.path()).expect("Failed to load config with peer overrides");
+
+        assert!(config.identity.encrypt);
+        assert_eq!(config.identity.key_type, KeyTypeConfig::Secp256k1);
+        assert_eq!(
+            config.network.listen_multiaddrs().unwrap(),
+            vec!["/ip4/0.0.0.0/tcp/4001".parse().unwrap()]
+        );
+
+        let alice = config.peers.get("alice").expect("Missing peer override");
+        assert_eq!(alice.peer_id.as_deref(), Some("12D3KooWExample"));
+        assert_eq!(alice.pre_shared_secret.as_deref(), Some("shh"));
+    }
+
+    #[test]
+    fn test_unknown_field_is_rejected() {
+        let file = write_config(
+            r#"
+            [identity]
+            totally_unknown_field = true
+            "#,
+        );
+
+        assert!(load_config(file.path()).is_err());
+    }
+
+    struct MockUserDirectoryProvider {
+        dir: PathBuf,
+    }
+
+    impl UserDirectoryProvider for MockUserDirectoryProvider {
+        fn get_config_dir(&self) -> Option<PathBuf> {
+            Some(self.dir.clone())
+        }
+    }
+
+    #[test]
+    fn test_configured_provider_honors_keypair_dir_override() {
+        let inner = MockUserDirectoryProvider { dir: PathBuf::from("/default/config/dir") };
+        let identity = IdentityConfig {
+            keypair_dir: Some(PathBuf::from("/custom/keypair/dir")),
+            encrypt: false,
+            key_type: KeyTypeConfig::Ed25519,
+            format: KeyFormatConfig::Base64,
+        };
+
+        let provider = ConfiguredUserDirectoryProvider::new(inner, Some(&identity));
+
+        assert_eq!(provider.get_config_dir(), Some(PathBuf::from("/custom/keypair/dir")));
+    }
+
+    #[test]
+    fn test_configured_provider_falls_back_without_override() {
+        let inner = MockUserDirectoryProvider { dir: PathBuf::from("/default/config/dir") };
+
+        let provider = ConfiguredUserDirectoryProvider::new(inner, None);
+
+        assert_eq!(provider.get_config_dir(), Some(PathBuf::from("/default/config/dir")));
+    }
+}
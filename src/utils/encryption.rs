@@ -0,0 +1,134 @@
+use std::error::Error;
+
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+
+/// Prefix identifying an encrypted keypair container. Legacy files (bare
+/// base64-encoded protobuf, no prefix) are left untouched for backward
+/// compatibility.
+const MAGIC: &[u8; 4] = b"CCK1";
+const VERSION_ENCRYPTED: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+
+/// Encrypts `plaintext` (the protobuf-encoded keypair) under a key derived
+/// from `passphrase` via argon2id, returning a self-describing container:
+/// `MAGIC ‖ VERSION ‖ salt ‖ nonce ‖ ciphertext`.
+pub fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut salt = [0u8; SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let key = derive_key(passphrase, &salt)?;
+
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+    let nonce = XNonce::from_slice(&nonce_bytes);
+
+    let cipher = XChaCha20Poly1305::new(&key.into());
+    let ciphertext = cipher
+        .encrypt(nonce, plaintext)
+        .map_err(|_| "Failed to encrypt keypair")?;
+
+    let mut container =
+        Vec::with_capacity(MAGIC.len() + 1 + SALT_LEN + NONCE_LEN + ciphertext.len());
+    container.extend_from_slice(MAGIC);
+    container.push(VERSION_ENCRYPTED);
+    container.extend_from_slice(&salt);
+    container.extend_from_slice(&nonce_bytes);
+    container.extend_from_slice(&ciphertext);
+
+    Ok(container)
+}
+
+/// Checks for the encrypted container's magic prefix without attempting
+/// decryption, so callers can tell "this needs a passphrase" apart from
+/// unrelated read failures (missing file, corrupt data, wrong format)
+/// before deciding whether to prompt.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Inspects `data` for the encrypted container's magic prefix. Returns
+/// `Ok(None)` when absent, meaning `data` is a legacy unencrypted protobuf
+/// blob that callers should use as-is. Returns `Ok(Some(plaintext))` when the
+/// container was found and successfully decrypted with `passphrase`.
+pub fn decrypt_if_encrypted(
+    data: &[u8],
+    passphrase: Option<&str>,
+) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+    if !is_encrypted(data) {
+        return Ok(None);
+    }
+
+    let version = data[MAGIC.len()];
+    if version != VERSION_ENCRYPTED {
+        return Err(format!("Unsupported keypair container version: {}", version).into());
+    }
+
+    let passphrase = passphrase.ok_or("This keypair is encrypted; a passphrase is required")?;
+
+    let rest = &data[MAGIC.len() + 1..];
+    if rest.len() < SALT_LEN + NONCE_LEN {
+        return Err("Corrupted keypair container".into());
+    }
+    let (salt, rest) = rest.split_at(SALT_LEN);
+    let (nonce_bytes, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let key = derive_key(passphrase, salt)?;
+    let nonce = XNonce::from_slice(nonce_bytes);
+    let cipher = XChaCha20Poly1305::new(&key.into());
+    let plaintext = cipher
+        .decrypt(nonce, ciphertext)
+        .map_err(|_| "Failed to decrypt keypair: wrong passphrase or corrupted file")?;
+
+    Ok(Some(plaintext))
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; KEY_LEN], Box<dyn Error>> {
+    let mut key = [0u8; KEY_LEN];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed to derive key: {}", e))?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        let plaintext = b"super secret keypair bytes";
+        let container = encrypt(plaintext, "correct horse battery staple").unwrap();
+
+        let decrypted = decrypt_if_encrypted(&container, Some("correct horse battery staple"))
+            .unwrap()
+            .expect("container should be detected as encrypted");
+
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn test_decrypt_wrong_passphrase_fails() {
+        let container = encrypt(b"super secret keypair bytes", "right passphrase").unwrap();
+
+        let result = decrypt_if_encrypted(&container, Some("wrong passphrase"));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_legacy_data_is_not_detected_as_encrypted() {
+        let legacy = b"not a container, just raw protobuf bytes";
+        let result = decrypt_if_encrypted(legacy, None).unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_decrypt_without_passphrase_errors() {
+        let container = encrypt(b"secret", "pw").unwrap();
+        let result = decrypt_if_encrypted(&container, None);
+        assert!(result.is_err());
+    }
+}
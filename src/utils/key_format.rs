@@ -0,0 +1,171 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use base64::{engine::general_purpose, Engine};
+use libp2p::identity::{secp256k1, Keypair};
+use pem::Pem;
+
+use super::encryption;
+
+const PEM_TAG: &str = "CHAINCRAFT PRIVATE KEY";
+
+/// On-disk encoding for a stored key, independent of its curve.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyFormat {
+    /// The original format: protobuf encoding, base64-armored.
+    Base64Protobuf,
+    /// PEM-armored protobuf encoding, tagged `CHAINCRAFT PRIVATE KEY`.
+    Pem,
+}
+
+/// Curve used for a node's identity keypair. Different bridged chains expect
+/// different curves, so this isn't fixed to ed25519.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyType {
+    Ed25519,
+    Secp256k1,
+}
+
+/// Generates a keypair of the requested curve, deterministically from
+/// `secret_key_seed` when provided, otherwise from fresh randomness.
+pub fn generate_keypair(
+    key_type: KeyType,
+    secret_key_seed: Option<[u8; 32]>,
+) -> Result<Keypair, Box<dyn Error>> {
+    match (key_type, secret_key_seed) {
+        (KeyType::Ed25519, Some(seed)) => Ok(Keypair::ed25519_from_bytes(seed)?),
+        (KeyType::Ed25519, None) => Ok(Keypair::generate_ed25519()),
+        (KeyType::Secp256k1, Some(mut seed)) => {
+            let secret_key = secp256k1::SecretKey::try_from_bytes(&mut seed)
+                .map_err(|e| format!("Invalid secp256k1 seed: {}", e))?;
+            Ok(secp256k1::Keypair::from(secret_key).into())
+        }
+        (KeyType::Secp256k1, None) => Ok(Keypair::generate_secp256k1()),
+    }
+}
+
+/// A key that can serialize/deserialize itself to/from a file in a chosen
+/// on-disk [`KeyFormat`], optionally encrypted at rest with a passphrase
+/// (see [`encryption`]).
+pub trait EncodableKey: Sized {
+    /// Canonical raw key bytes, before any format armoring or encryption.
+    fn to_raw_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>>;
+    fn from_raw_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>>;
+
+    fn write_to_path(
+        &self,
+        path: &Path,
+        format: KeyFormat,
+        passphrase: Option<&str>,
+    ) -> Result<(), Box<dyn Error>> {
+        let raw = self.to_raw_bytes()?;
+        let stored = match passphrase {
+            Some(passphrase) => encryption::encrypt(&raw, passphrase)?,
+            None => raw,
+        };
+
+        let mut file = File::create(path)?;
+        file.write_all(&encode_bytes(&stored, format))?;
+        Ok(())
+    }
+
+    fn read_from_path(
+        path: &Path,
+        format: KeyFormat,
+        passphrase: Option<&str>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut contents = Vec::new();
+        File::open(path)?.read_to_end(&mut contents)?;
+        let stored = decode_bytes(&contents, format)?;
+
+        let raw = match encryption::decrypt_if_encrypted(&stored, passphrase)? {
+            Some(plaintext) => plaintext,
+            None => stored,
+        };
+
+        Self::from_raw_bytes(&raw)
+    }
+
+    /// Checks whether the key stored at `path` is encrypted, without
+    /// attempting decryption. Lets callers decide whether a
+    /// [`read_from_path`](Self::read_from_path) failure is actually a
+    /// missing passphrase, as opposed to a missing file or corrupt data.
+    fn is_encrypted_at(path: &Path, format: KeyFormat) -> Result<bool, Box<dyn Error>> {
+        let mut contents = Vec::new();
+        File::open(path)?.read_to_end(&mut contents)?;
+        let stored = decode_bytes(&contents, format)?;
+        Ok(encryption::is_encrypted(&stored))
+    }
+}
+
+impl EncodableKey for Keypair {
+    fn to_raw_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        Ok(self.to_protobuf_encoding()?)
+    }
+
+    fn from_raw_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        Ok(Keypair::from_protobuf_encoding(bytes)?)
+    }
+}
+
+fn encode_bytes(bytes: &[u8], format: KeyFormat) -> Vec<u8> {
+    match format {
+        KeyFormat::Base64Protobuf => general_purpose::STANDARD.encode(bytes).into_bytes(),
+        KeyFormat::Pem => pem::encode(&Pem::new(PEM_TAG, bytes.to_vec())).into_bytes(),
+    }
+}
+
+fn decode_bytes(data: &[u8], format: KeyFormat) -> Result<Vec<u8>, Box<dyn Error>> {
+    match format {
+        KeyFormat::Base64Protobuf => Ok(general_purpose::STANDARD.decode(data)?),
+        KeyFormat::Pem => {
+            let text = std::str::from_utf8(data)?;
+            let parsed = pem::parse(text)?;
+            Ok(parsed.contents().to_vec())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ed25519_roundtrip_base64_protobuf() {
+        let keypair = generate_keypair(KeyType::Ed25519, None).unwrap();
+        let raw = keypair.to_raw_bytes().unwrap();
+        let encoded = encode_bytes(&raw, KeyFormat::Base64Protobuf);
+        let decoded = decode_bytes(&encoded, KeyFormat::Base64Protobuf).unwrap();
+
+        assert_eq!(raw, decoded);
+    }
+
+    #[test]
+    fn test_ed25519_roundtrip_pem() {
+        let keypair = generate_keypair(KeyType::Ed25519, None).unwrap();
+        let raw = keypair.to_raw_bytes().unwrap();
+        let encoded = encode_bytes(&raw, KeyFormat::Pem);
+        let decoded = decode_bytes(&encoded, KeyFormat::Pem).unwrap();
+
+        assert_eq!(raw, decoded);
+    }
+
+    #[test]
+    fn test_secp256k1_keypair_is_generated() {
+        let keypair_a = generate_keypair(KeyType::Secp256k1, None).unwrap();
+        let keypair_b = generate_keypair(KeyType::Secp256k1, None).unwrap();
+
+        assert_ne!(keypair_a.public(), keypair_b.public());
+    }
+
+    #[test]
+    fn test_secp256k1_from_seed_is_deterministic() {
+        let seed = [3u8; 32];
+        let keypair_a = generate_keypair(KeyType::Secp256k1, Some(seed)).unwrap();
+        let keypair_b = generate_keypair(KeyType::Secp256k1, Some(seed)).unwrap();
+
+        assert_eq!(keypair_a.public(), keypair_b.public());
+    }
+}
@@ -0,0 +1,74 @@
+use std::error::Error;
+
+use bip39::Mnemonic;
+use rand::RngCore;
+
+/// Entropy length (in bytes) for a 24-word mnemonic (256 bits), the
+/// strongest BIP39 phrase size.
+const ENTROPY_LEN: usize = 32;
+
+/// Generates a fresh 24-word BIP39 mnemonic from 256 bits of randomness.
+/// The caller is responsible for displaying it to the user exactly once so
+/// it can be backed up; it is never persisted to disk.
+pub fn generate_mnemonic() -> Result<Mnemonic, Box<dyn Error>> {
+    let mut entropy = [0u8; ENTROPY_LEN];
+    rand::thread_rng().fill_bytes(&mut entropy);
+    Ok(Mnemonic::from_entropy(&entropy)?)
+}
+
+/// Validates `phrase` against the BIP39 word list and checksum, then derives
+/// the 64-byte seed via PBKDF2-HMAC-SHA512 (2048 iterations, salt
+/// `"mnemonic" ‖ passphrase`), as specified by BIP39.
+pub fn mnemonic_to_seed(phrase: &str, passphrase: &str) -> Result<[u8; 64], Box<dyn Error>> {
+    let mnemonic: Mnemonic = phrase
+        .parse()
+        .map_err(|e| format!("Invalid mnemonic phrase: {}", e))?;
+
+    Ok(mnemonic.to_seed(passphrase))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_mnemonic_has_24_words() {
+        let mnemonic = generate_mnemonic().unwrap();
+        assert_eq!(mnemonic.word_count(), 24);
+    }
+
+    #[test]
+    fn test_generate_mnemonic_is_unique() {
+        let a = generate_mnemonic().unwrap();
+        let b = generate_mnemonic().unwrap();
+        assert_ne!(a.to_string(), b.to_string());
+    }
+
+    #[test]
+    fn test_mnemonic_to_seed_is_deterministic() {
+        let mnemonic = generate_mnemonic().unwrap();
+        let phrase = mnemonic.to_string();
+
+        let seed_a = mnemonic_to_seed(&phrase, "").unwrap();
+        let seed_b = mnemonic_to_seed(&phrase, "").unwrap();
+
+        assert_eq!(seed_a, seed_b);
+    }
+
+    #[test]
+    fn test_mnemonic_to_seed_differs_with_passphrase() {
+        let mnemonic = generate_mnemonic().unwrap();
+        let phrase = mnemonic.to_string();
+
+        let seed_without = mnemonic_to_seed(&phrase, "").unwrap();
+        let seed_with = mnemonic_to_seed(&phrase, "extra words").unwrap();
+
+        assert_ne!(seed_without, seed_with);
+    }
+
+    #[test]
+    fn test_invalid_mnemonic_is_rejected() {
+        let result = mnemonic_to_seed("not a valid bip39 mnemonic phrase at all", "");
+        assert!(result.is_err());
+    }
+}
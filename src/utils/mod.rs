@@ -0,0 +1,8 @@
+pub mod config;
+pub mod encryption;
+pub mod key_format;
+pub mod mnemonic;
+pub mod node_record;
+pub mod peer_id;
+pub mod platform;
+pub mod seed;
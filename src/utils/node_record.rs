@@ -0,0 +1,223 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use libp2p::identity::PublicKey;
+use libp2p::{Multiaddr, PeerId};
+use serde::{Deserialize, Serialize};
+
+use super::key_format::KeyFormat;
+use super::peer_id::{load_keypair_with_format, UserDirectoryProvider};
+
+const NODE_RECORD_FILE_NAME: &str = "node_record.enr";
+
+/// The signed, versioned part of a [`NodeRecord`]: everything but the
+/// signature itself. Kept separate so signing/verification always operate
+/// on the exact same serialization.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct NodeRecordPayload {
+    seq: u64,
+    addrs: Vec<String>,
+    pairs: BTreeMap<String, String>,
+    public_key: Vec<u8>,
+}
+
+/// A signed, discoverable node record, inspired by devp2p's EIP-868 ENR: a
+/// monotonically increasing sequence number, the node's advertised
+/// multiaddrs, and arbitrary key/value pairs, signed with the node's
+/// identity keypair so peers can verify it came from the PeerId it claims.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeRecord {
+    payload: NodeRecordPayload,
+    signature: Vec<u8>,
+}
+
+impl NodeRecord {
+    pub fn seq(&self) -> u64 {
+        self.payload.seq
+    }
+
+    pub fn addrs(&self) -> Result<Vec<Multiaddr>, Box<dyn Error>> {
+        self.payload
+            .addrs
+            .iter()
+            .map(|addr| addr.parse().map_err(|e| format!("Invalid multiaddr: {}", e).into()))
+            .collect()
+    }
+
+    pub fn pairs(&self) -> &BTreeMap<String, String> {
+        &self.payload.pairs
+    }
+
+    pub fn peer_id(&self) -> Result<PeerId, Box<dyn Error>> {
+        Ok(PeerId::from(decode_public_key(&self.payload.public_key)?))
+    }
+}
+
+/// Creates a fresh [`NodeRecord`] for `addrs`/`pairs` at sequence number
+/// `seq`, signs it with the node's stored identity keypair (read in the
+/// given `format`, decrypted with `passphrase` if needed), and persists it
+/// (base64-encoded) next to the keypair file. Callers must increment `seq`
+/// on every call that changes `addrs` or `pairs`, or peers with a copy of
+/// the previous record may prefer it over the update.
+pub fn generate_node_record<T: UserDirectoryProvider>(
+    addrs: Vec<Multiaddr>,
+    seq: u64,
+    pairs: BTreeMap<String, String>,
+    format: KeyFormat,
+    passphrase: Option<&str>,
+    provider: &T,
+) -> Result<NodeRecord, Box<dyn Error>> {
+    let keypair = load_keypair_with_format(provider, format, passphrase)?;
+
+    let payload = NodeRecordPayload {
+        seq,
+        addrs: addrs.iter().map(|addr| addr.to_string()).collect(),
+        pairs,
+        public_key: keypair.public().encode_protobuf(),
+    };
+    let signature = keypair.sign(&signing_bytes(&payload)?)?;
+    let record = NodeRecord { payload, signature };
+
+    save_node_record(&record, provider)?;
+    Ok(record)
+}
+
+/// Loads the persisted [`NodeRecord`] and verifies its signature against the
+/// node's stored identity keypair (read in the given `format`, decrypted
+/// with `passphrase` if needed), rejecting any record that was tampered
+/// with, whose signature doesn't match its own embedded public key, or whose
+/// embedded public key doesn't match the keypair actually stored on disk
+/// (e.g. a record substituted by an attacker who can write to the config
+/// directory but not read the keypair).
+pub fn read_node_record<T: UserDirectoryProvider>(
+    format: KeyFormat,
+    passphrase: Option<&str>,
+    provider: &T,
+) -> Result<NodeRecord, Box<dyn Error>> {
+    let config_dir = provider
+        .get_config_dir()
+        .ok_or("Config directory not found")?;
+    let file_path = config_dir.join(NODE_RECORD_FILE_NAME);
+
+    let encoded = fs::read_to_string(file_path)?;
+    let record: NodeRecord = serde_json::from_slice(&STANDARD.decode(encoded.trim())?)?;
+
+    verify_signature(&record)?;
+
+    let stored_keypair = load_keypair_with_format(provider, format, passphrase)?;
+    if record.peer_id()? != PeerId::from(stored_keypair.public()) {
+        return Err("Node record public key does not match the stored keypair".into());
+    }
+
+    Ok(record)
+}
+
+fn save_node_record<T: UserDirectoryProvider>(
+    record: &NodeRecord,
+    provider: &T,
+) -> Result<(), Box<dyn Error>> {
+    let config_dir = provider
+        .get_config_dir()
+        .ok_or("Config directory not found")?;
+    fs::create_dir_all(&config_dir)?;
+    provider.restrict_dir_permissions(&config_dir)?;
+
+    let file_path = config_dir.join(NODE_RECORD_FILE_NAME);
+    let encoded = STANDARD.encode(serde_json::to_vec(record)?);
+    fs::write(&file_path, encoded)?;
+    provider.restrict_file_permissions(&file_path)?;
+
+    Ok(())
+}
+
+fn verify_signature(record: &NodeRecord) -> Result<(), Box<dyn Error>> {
+    let public_key = decode_public_key(&record.payload.public_key)?;
+    let message = signing_bytes(&record.payload)?;
+
+    if public_key.verify(&message, &record.signature) {
+        Ok(())
+    } else {
+        Err("Node record signature does not match its embedded public key".into())
+    }
+}
+
+fn decode_public_key(bytes: &[u8]) -> Result<PublicKey, Box<dyn Error>> {
+    Ok(PublicKey::try_decode_protobuf(bytes)?)
+}
+
+fn signing_bytes(payload: &NodeRecordPayload) -> Result<Vec<u8>, Box<dyn Error>> {
+    Ok(serde_json::to_vec(payload)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::peer_id::generate_new_keypair_and_peer_id;
+    use tempfile::TempDir;
+
+    struct MockUserDirectoryProvider {
+        temp_dir: TempDir,
+    }
+
+    impl MockUserDirectoryProvider {
+        fn new() -> Self {
+            MockUserDirectoryProvider {
+                temp_dir: TempDir::new().expect("Failed to create temp directory"),
+            }
+        }
+    }
+
+    impl UserDirectoryProvider for MockUserDirectoryProvider {
+        fn get_config_dir(&self) -> Option<std::path::PathBuf> {
+            Some(self.temp_dir.path().to_path_buf())
+        }
+    }
+
+    fn provider_with_keypair() -> MockUserDirectoryProvider {
+        let provider = MockUserDirectoryProvider::new();
+        generate_new_keypair_and_peer_id(None, &provider).expect("Failed to seed test keypair");
+        provider
+    }
+
+    #[test]
+    fn test_generate_and_read_node_record_roundtrip() {
+        let provider = provider_with_keypair();
+        let addrs = vec!["/ip4/127.0.0.1/tcp/4001".parse().unwrap()];
+        let mut pairs = BTreeMap::new();
+        pairs.insert("chain".to_string(), "ethereum".to_string());
+
+        generate_node_record(addrs.clone(), 1, pairs.clone(), KeyFormat::Base64Protobuf, None, &provider)
+            .expect("Failed to generate node record");
+
+        let record = read_node_record(KeyFormat::Base64Protobuf, None, &provider)
+            .expect("Failed to read node record");
+
+        assert_eq!(record.seq(), 1);
+        assert_eq!(record.addrs().unwrap(), addrs);
+        assert_eq!(record.pairs(), &pairs);
+    }
+
+    #[test]
+    fn test_bumped_seq_is_persisted() {
+        let provider = provider_with_keypair();
+
+        generate_node_record(vec![], 1, BTreeMap::new(), KeyFormat::Base64Protobuf, None, &provider).unwrap();
+        generate_node_record(vec![], 2, BTreeMap::new(), KeyFormat::Base64Protobuf, None, &provider).unwrap();
+
+        let record = read_node_record(KeyFormat::Base64Protobuf, None, &provider).unwrap();
+        assert_eq!(record.seq(), 2);
+    }
+
+    #[test]
+    fn test_tampered_record_fails_verification() {
+        let provider = provider_with_keypair();
+        let mut record =
+            generate_node_record(vec![], 1, BTreeMap::new(), KeyFormat::Base64Protobuf, None, &provider).unwrap();
+
+        record.payload.seq = 99;
+
+        assert!(verify_signature(&record).is_err());
+    }
+}
@@ -1,33 +1,60 @@
 use std::error::Error;
-use std::fs::{create_dir_all, set_permissions, File, Permissions};
-use std::io::{Read, Write};
-use std::os::unix::fs::PermissionsExt;
-use std::path::PathBuf;
-
-use base64::{engine::general_purpose, Engine};
-use directories::UserDirs;
-use general_purpose::STANDARD;
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+
+use directories::ProjectDirs;
 use libp2p::identity::Keypair;
 use libp2p::PeerId;
+use rand::RngCore;
+use rpassword::prompt_password;
 use sha3::{Digest, Sha3_256};
 
-static DEFAULT_PATH: &[&str] = &[".chaincraft", "keypair.key"];
-const PATH_PERMISSIONS: u32 = 0o700;
-const FILE_PERMISSIONS: u32 = 0o600;
+use super::key_format::{EncodableKey, KeyFormat, KeyType};
+use super::mnemonic;
+use super::platform;
+use super::seed::Seed;
 
+const KEYPAIR_FILE_NAME: &str = "keypair.key";
+const MASTER_SEED_FILE_NAME: &str = "master_seed.key";
+const CONFIG_DIR_OVERRIDE_ENV: &str = "CHAINCRAFT_CONFIG_DIR";
 
+/// The single seam for every OS-specific path and permission decision:
+/// where chaincraft's config directory lives, and how a created
+/// directory/file gets locked down to the current user.
 pub trait UserDirectoryProvider {
-    fn get_user_home_dir(&self) -> Option<PathBuf>;
+    /// Directory chaincraft stores its keypair and other config under.
+    fn get_config_dir(&self) -> Option<PathBuf>;
+
+    /// Restricts a freshly created directory to the current user. Unix uses
+    /// mode `0o700`; Windows tightens the ACL instead.
+    fn restrict_dir_permissions(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        platform::restrict_dir_permissions(path)
+    }
+
+    /// Restricts a freshly created file to the current user. Unix uses mode
+    /// `0o600`; Windows tightens the ACL instead.
+    fn restrict_file_permissions(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        platform::restrict_file_permissions(path)
+    }
 }
 
 pub struct DefaultUserDirectoryProvider;
 
 impl UserDirectoryProvider for DefaultUserDirectoryProvider {
-    fn get_user_home_dir(&self) -> Option<PathBuf> {
-        UserDirs::new().map(|user_dirs| user_dirs.home_dir().to_path_buf())
+    fn get_config_dir(&self) -> Option<PathBuf> {
+        if let Ok(override_dir) = std::env::var(CONFIG_DIR_OVERRIDE_ENV) {
+            return Some(PathBuf::from(override_dir));
+        }
+
+        // `~/.config/chaincraft` on Linux, `%APPDATA%\chaincraft\config` on
+        // Windows, `~/Library/Application Support/chaincraft` on macOS.
+        ProjectDirs::from("", "", "chaincraft").map(|dirs| dirs.config_dir().to_path_buf())
     }
 }
 
+/// Legacy, non-standard seeding: SHA3-256-hashes an arbitrary string. Kept
+/// only for the `--legacy-seed` escape hatch; prefer BIP39 mnemonics via
+/// [`generate_new_keypair_and_peer_id_from_mnemonic`].
 fn seed_phrase_to_bytes(seed_phrase: Option<&str>) -> Option<[u8; 32]> {
     let seed = seed_phrase?;
     let mut hasher = Sha3_256::new();
@@ -37,77 +64,205 @@ fn seed_phrase_to_bytes(seed_phrase: Option<&str>) -> Option<[u8; 32]> {
     result.as_slice().try_into().ok()
 }
 
-fn generate_keypair(secret_key_seed: Option<[u8; 32]>) -> Keypair {
-    match secret_key_seed {
-        Some(seed) => Keypair::ed25519_from_bytes(seed).unwrap(),
-        None => Keypair::generate_ed25519(),
-    }
-}
-
-fn save_keypair<T: UserDirectoryProvider>(keypair: &Keypair, provider: &T) -> Result<(), Box<dyn Error>> {
-    // Encode as protobuf structure.
-    let encoded_keypair_pbuf = keypair.to_protobuf_encoding()?;
+/// Writes `key` to the default keypair path in the given on-disk `format`,
+/// generic over any [`EncodableKey`] (e.g. different curves). Encrypts at
+/// rest when `passphrase` is supplied, otherwise keeps the legacy behavior.
+fn save_keypair_with_format<K: EncodableKey, T: UserDirectoryProvider>(
+    key: &K,
+    format: KeyFormat,
+    passphrase: Option<&str>,
+    provider: &T,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(config_dir) = provider.get_config_dir() {
+        create_dir_all(&config_dir)?;
+        provider.restrict_dir_permissions(&config_dir)?;
 
-    // Encode as base64
-    let encoded_keypair_pbuf_base64 = general_purpose::STANDARD.encode(&encoded_keypair_pbuf);
+        let file_path = config_dir.join(KEYPAIR_FILE_NAME);
+        key.write_to_path(&file_path, format, passphrase)?;
+        provider.restrict_file_permissions(&file_path)?;
 
-    // Save encoded keypair to file
-    if let Some(home_dir) = provider.get_user_home_dir() {
-        let file_path = DEFAULT_PATH
-            .iter()
-            .fold(home_dir.to_path_buf(), |path, component| {
-                path.join(component)
-            });
+        Ok(())
+    } else {
+        Err("Config directory not found".into())
+    }
+}
 
-        if let Some(parent_dir) = file_path.parent() {
-            create_dir_all(parent_dir)?;
-            set_permissions(parent_dir, Permissions::from_mode(PATH_PERMISSIONS))?;
-        }
+/// Persists `seed` as the node's master seed, encrypted at rest when
+/// `passphrase` is supplied. Individual keys (the libp2p identity, and
+/// eventually per-chain signing keys) are re-derived from it on every load,
+/// so the master seed is the only secret that ever touches the filesystem
+/// for the `--seed-phrase`/BIP39 generation paths.
+fn save_master_seed<T: UserDirectoryProvider>(
+    seed: &Seed,
+    format: KeyFormat,
+    passphrase: Option<&str>,
+    provider: &T,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(config_dir) = provider.get_config_dir() {
+        create_dir_all(&config_dir)?;
+        provider.restrict_dir_permissions(&config_dir)?;
 
-        let mut file = File::create(&file_path)?;
-        file.write_all(encoded_keypair_pbuf_base64.as_bytes())?;
-        set_permissions(file_path, Permissions::from_mode(FILE_PERMISSIONS))?;
+        let file_path = config_dir.join(MASTER_SEED_FILE_NAME);
+        seed.write_to_path(&file_path, format, passphrase)?;
+        provider.restrict_file_permissions(&file_path)?;
 
         Ok(())
     } else {
-        Err("Home directory not found".into())
+        Err("Config directory not found".into())
     }
 }
 
-pub fn generate_peer_id<T: UserDirectoryProvider>(provider: &T) -> Result<PeerId, Box<dyn Error>> {
-    if let Some(home_dir) = provider.get_user_home_dir() {
-        let file_path = DEFAULT_PATH
-            .iter()
-            .fold(home_dir.to_path_buf(), |path, component| {
-                path.join(component)
-            });
-        let mut file = File::open(file_path)?;
+/// Loads the persisted master seed, prompting for a passphrase if the file
+/// turns out to be encrypted and none was supplied upfront.
+fn load_master_seed<T: UserDirectoryProvider>(
+    provider: &T,
+    format: KeyFormat,
+    passphrase: Option<&str>,
+) -> Result<Seed, Box<dyn Error>> {
+    let config_dir = provider
+        .get_config_dir()
+        .ok_or("Config directory not found")?;
+    let file_path = config_dir.join(MASTER_SEED_FILE_NAME);
+
+    read_encodable_with_retry(&file_path, format, passphrase)
+}
 
-        let mut encoded_secret_base64 = String::new();
-        file.read_to_string(&mut encoded_secret_base64)?;
+/// Reads an [`EncodableKey`] from `path`, prompting for a passphrase if the
+/// file turns out to be encrypted and none was supplied upfront. Any other
+/// failure (missing file, corrupt data, wrong format) is returned as-is
+/// without prompting.
+fn read_encodable_with_retry<K: EncodableKey>(
+    path: &Path,
+    format: KeyFormat,
+    passphrase: Option<&str>,
+) -> Result<K, Box<dyn Error>> {
+    match K::read_from_path(path, format, passphrase) {
+        Ok(key) => Ok(key),
+        Err(_e) if passphrase.is_none() && K::is_encrypted_at(path, format).unwrap_or(false) => {
+            let prompted = prompt_password("Keypair passphrase: ")?;
+            K::read_from_path(path, format, Some(&prompted))
+        }
+        Err(e) => Err(e),
+    }
+}
 
-        let encoded_secret = STANDARD.decode(&encoded_secret_base64)?;
-        let keypair = Keypair::from_protobuf_encoding(&encoded_secret)?;
+/// Accepts a passphrase upfront for keypairs stored encrypted. If the
+/// stored file turns out to be encrypted and no passphrase was supplied,
+/// the user is prompted for one interactively.
+pub fn generate_peer_id_with_passphrase<T: UserDirectoryProvider>(
+    provider: &T,
+    passphrase: Option<&str>,
+) -> Result<PeerId, Box<dyn Error>> {
+    generate_peer_id_with_format(provider, KeyFormat::Base64Protobuf, passphrase)
+}
 
-        Ok(PeerId::from(keypair.public()))
-    } else {
-        Err("Home directory not found".into())
+/// Like [`generate_peer_id_with_passphrase`], but reads the keypair file in
+/// the given on-disk `format`.
+pub fn generate_peer_id_with_format<T: UserDirectoryProvider>(
+    provider: &T,
+    format: KeyFormat,
+    passphrase: Option<&str>,
+) -> Result<PeerId, Box<dyn Error>> {
+    let keypair = load_keypair_with_format(provider, format, passphrase)?;
+    Ok(PeerId::from(keypair.public()))
+}
+
+/// Loads and decodes the stored keypair, prompting for a passphrase if the
+/// file turns out to be encrypted and none was supplied upfront. Used
+/// wherever the full keypair (not just the derived [`PeerId`]) is needed,
+/// e.g. to sign a [`super::node_record::NodeRecord`].
+///
+/// If a master seed (see [`save_master_seed`]) was persisted instead of a
+/// raw keypair, the libp2p identity is re-derived from it rather than read
+/// directly; otherwise falls back to the legacy keypair file.
+pub fn load_keypair_with_format<T: UserDirectoryProvider>(
+    provider: &T,
+    format: KeyFormat,
+    passphrase: Option<&str>,
+) -> Result<Keypair, Box<dyn Error>> {
+    let config_dir = provider
+        .get_config_dir()
+        .ok_or("Config directory not found")?;
+
+    if config_dir.join(MASTER_SEED_FILE_NAME).exists() {
+        return Ok(load_master_seed(provider, format, passphrase)?.derive_libp2p_identity());
     }
+
+    let file_path = config_dir.join(KEYPAIR_FILE_NAME);
+    read_encodable_with_retry(&file_path, format, passphrase)
 }
 
 pub fn generate_new_keypair_and_peer_id<T: UserDirectoryProvider>(
     seed_phrase: Option<&str>,
     provider: &T,
 ) -> Result<PeerId, Box<dyn Error>> {
-    let secret_key_seed = seed_phrase_to_bytes(seed_phrase);
-    let keypair = generate_keypair(secret_key_seed);
-    save_keypair(&keypair, provider)?;
-    generate_peer_id(provider)
+    generate_new_keypair_and_peer_id_with_encryption(seed_phrase, KeyFormat::Base64Protobuf, None, provider)
+}
+
+/// Like [`generate_new_keypair_and_peer_id`], but persists only the master
+/// seed in the given on-disk `format`, encrypted at rest when `passphrase`
+/// is `Some`; the libp2p identity keypair itself is re-derived from it on
+/// every load (see [`Seed::derive_libp2p_identity`]) rather than written to
+/// disk.
+pub fn generate_new_keypair_and_peer_id_with_encryption<T: UserDirectoryProvider>(
+    seed_phrase: Option<&str>,
+    format: KeyFormat,
+    passphrase: Option<&str>,
+    provider: &T,
+) -> Result<PeerId, Box<dyn Error>> {
+    let secret_key_seed = seed_phrase_to_bytes(seed_phrase).unwrap_or_else(|| {
+        let mut random_seed = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut random_seed);
+        random_seed
+    });
+    let seed = Seed::new(secret_key_seed);
+
+    save_master_seed(&seed, format, passphrase, provider)?;
+    generate_peer_id_with_format(provider, format, passphrase)
+}
+
+/// Derives a master seed from a BIP39 mnemonic (validating its checksum and
+/// word list) plus an optional mnemonic passphrase, taking the first 32
+/// bytes of the 64-byte BIP39 seed. `passphrase` (distinct from the mnemonic
+/// passphrase) optionally encrypts the persisted master seed at rest; it is
+/// stored in the given on-disk `format`, and the libp2p identity keypair is
+/// re-derived from it on every load rather than written to disk.
+pub fn generate_new_keypair_and_peer_id_from_mnemonic<T: UserDirectoryProvider>(
+    mnemonic_phrase: &str,
+    mnemonic_passphrase: Option<&str>,
+    format: KeyFormat,
+    passphrase: Option<&str>,
+    provider: &T,
+) -> Result<PeerId, Box<dyn Error>> {
+    let bip39_seed = mnemonic::mnemonic_to_seed(mnemonic_phrase, mnemonic_passphrase.unwrap_or(""))?;
+
+    let mut secret_key_seed = [0u8; 32];
+    secret_key_seed.copy_from_slice(&bip39_seed[..32]);
+    let seed = Seed::new(secret_key_seed);
+
+    save_master_seed(&seed, format, passphrase, provider)?;
+    generate_peer_id_with_format(provider, format, passphrase)
+}
+
+/// Generalized keypair generation: picks the curve via `key_type` and the
+/// on-disk encoding via `format`, for chains that don't use ed25519. Only
+/// supports a raw seed (not BIP39 mnemonics, which remain ed25519-only).
+pub fn generate_new_keypair_and_peer_id_with_options<T: UserDirectoryProvider>(
+    key_type: KeyType,
+    format: KeyFormat,
+    secret_key_seed: Option<[u8; 32]>,
+    passphrase: Option<&str>,
+    provider: &T,
+) -> Result<PeerId, Box<dyn Error>> {
+    let keypair = crate::utils::key_format::generate_keypair(key_type, secret_key_seed)?;
+    save_keypair_with_format(&keypair, format, passphrase, provider)?;
+    generate_peer_id_with_format(provider, format, passphrase)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use base64::{engine::general_purpose::STANDARD, Engine};
     use std::fs;
     use tempfile::TempDir;
 
@@ -128,23 +283,21 @@ mod tests {
     }
 
     impl UserDirectoryProvider for MockUserDirectoryProvider {
-        fn get_user_home_dir(&self) -> Option<PathBuf> {
+        fn get_config_dir(&self) -> Option<PathBuf> {
             Some(self.temp_dir.path().to_path_buf())
         }
     }
 
     #[test]
-    fn test_get_user_home_dir() {
+    fn test_get_config_dir() {
         let provider = DefaultUserDirectoryProvider;
-        let home_dir = provider.get_user_home_dir();
+        let config_dir = provider.get_config_dir();
 
-        match home_dir {
+        match config_dir {
             Some(path) => {
-                assert!(path.exists(), "Directory does not exist.");
-                assert!(path.is_dir(), "Path is not a directory.");
-                println!("Home directory detected : {:?}", path);
+                println!("Config directory resolved: {:?}", path);
             }
-            None => panic!("Unable to detect home directory."),
+            None => panic!("Unable to resolve a config directory."),
         }
     }
 
@@ -187,8 +340,8 @@ mod tests {
             25, 26, 27, 28, 29, 30, 31, 32,
         ];
 
-        let keypair_a = generate_keypair(Some(seed));
-        let keypair_b = generate_keypair(Some(seed));
+        let keypair_a = crate::utils::key_format::generate_keypair(KeyType::Ed25519, Some(seed)).unwrap();
+        let keypair_b = crate::utils::key_format::generate_keypair(KeyType::Ed25519, Some(seed)).unwrap();
 
         assert_eq!(
             keypair_a.public(), keypair_b.public(),
@@ -198,8 +351,8 @@ mod tests {
 
     #[test]
     fn test_generate_keypair_without_seed() {
-        let keypair_a = generate_keypair(None);
-        let keypair_b = generate_keypair(None);
+        let keypair_a = crate::utils::key_format::generate_keypair(KeyType::Ed25519, None).unwrap();
+        let keypair_b = crate::utils::key_format::generate_keypair(KeyType::Ed25519, None).unwrap();
 
         assert_ne!(
             keypair_a.public(), keypair_b.public(),
@@ -210,17 +363,15 @@ mod tests {
     #[test]
     fn test_save_keypair() {
         let provider = MockUserDirectoryProvider::new();
-        let keypair = generate_keypair(None);
+        let keypair = crate::utils::key_format::generate_keypair(KeyType::Ed25519, None).unwrap();
 
-        let result = save_keypair(&keypair, &provider);
+        let result = save_keypair_with_format(&keypair, KeyFormat::Base64Protobuf, None, &provider);
 
         // Check that the result is Ok
         assert!(result.is_ok(), "Failed to save keypair: {:?}", result);
 
         // Check that the key file has been created
-        let file_path = DEFAULT_PATH.iter().fold(
-            provider.get_temp_dir_path(), |path, component| path.join(component)
-        );
+        let file_path = provider.get_temp_dir_path().join(KEYPAIR_FILE_NAME);
         assert!(file_path.exists(), "Key file has not been created.");
 
         // Check that the content of the key file is correct
@@ -234,20 +385,156 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_save_and_load_encrypted_keypair() {
+        let provider = MockUserDirectoryProvider::new();
+        let keypair = crate::utils::key_format::generate_keypair(KeyType::Ed25519, None).unwrap();
+
+        save_keypair_with_format(&keypair, KeyFormat::Base64Protobuf, Some("correct horse battery staple"), &provider)
+            .expect("Failed to save encrypted keypair");
+
+        let peer_id = generate_peer_id_with_passphrase(&provider, Some("correct horse battery staple"))
+            .expect("Failed to read encrypted keypair with correct passphrase");
+
+        assert_eq!(peer_id, PeerId::from(keypair.public()));
+    }
+
+    #[test]
+    fn test_reading_missing_keypair_does_not_prompt_for_passphrase() {
+        // No keypair has been saved to this provider's directory yet. This
+        // must fail with the underlying "no such file" error rather than
+        // blocking on a passphrase prompt, which would hang a non-interactive
+        // `--read-peer-id` run.
+        let provider = MockUserDirectoryProvider::new();
+
+        let result = generate_peer_id_with_format(&provider, KeyFormat::Base64Protobuf, None);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_load_encrypted_keypair_with_wrong_passphrase_fails() {
+        let provider = MockUserDirectoryProvider::new();
+        let keypair = crate::utils::key_format::generate_keypair(KeyType::Ed25519, None).unwrap();
+
+        save_keypair_with_format(&keypair, KeyFormat::Base64Protobuf, Some("right passphrase"), &provider)
+            .expect("Failed to save encrypted keypair");
+
+        let result = generate_peer_id_with_passphrase(&provider, Some("wrong passphrase"));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_generate_new_peer_id_with_pem_format() {
+        let provider = MockUserDirectoryProvider::new();
+
+        let peer_id = generate_new_keypair_and_peer_id_with_options(
+            KeyType::Ed25519,
+            KeyFormat::Pem,
+            None,
+            None,
+            &provider,
+        )
+        .expect("Failed to generate Peer ID with PEM format");
+
+        let reread = generate_peer_id_with_format(&provider, KeyFormat::Pem, None)
+            .expect("Failed to re-read PEM-stored keypair");
+
+        assert_eq!(peer_id, reread);
+    }
+
+    #[test]
+    fn test_generate_new_peer_id_with_secp256k1() {
+        let provider = MockUserDirectoryProvider::new();
+
+        let result = generate_new_keypair_and_peer_id_with_options(
+            KeyType::Secp256k1,
+            KeyFormat::Base64Protobuf,
+            None,
+            None,
+            &provider,
+        );
+
+        assert!(result.is_ok(), "Expected PeerId, got error: {:?}", result);
+    }
+
+    #[test]
+    fn test_generate_new_peer_id_from_mnemonic_with_pem_format() {
+        let mnemonic = mnemonic::generate_mnemonic().unwrap().to_string();
+        let provider = MockUserDirectoryProvider::new();
+
+        let peer_id = generate_new_keypair_and_peer_id_from_mnemonic(
+            &mnemonic,
+            None,
+            KeyFormat::Pem,
+            None,
+            &provider,
+        )
+        .expect("Failed to generate Peer ID from mnemonic with PEM format");
+
+        let reread = generate_peer_id_with_format(&provider, KeyFormat::Pem, None)
+            .expect("Failed to re-read PEM-stored master seed");
+
+        assert_eq!(peer_id, reread);
+    }
+
+    #[test]
+    fn test_generate_new_peer_id_from_mnemonic() {
+        let mnemonic = mnemonic::generate_mnemonic().unwrap().to_string();
+        let provider = MockUserDirectoryProvider::new();
+
+        let result =
+            generate_new_keypair_and_peer_id_from_mnemonic(&mnemonic, None, KeyFormat::Base64Protobuf, None, &provider);
+
+        assert!(result.is_ok(), "Expected PeerId, got error: {:?}", result);
+    }
+
+    #[test]
+    fn test_generate_new_peer_id_from_mnemonic_is_deterministic() {
+        let mnemonic = mnemonic::generate_mnemonic().unwrap().to_string();
+
+        let provider_a = MockUserDirectoryProvider::new();
+        let peer_id_a =
+            generate_new_keypair_and_peer_id_from_mnemonic(&mnemonic, None, KeyFormat::Base64Protobuf, None, &provider_a)
+                .unwrap();
+
+        let provider_b = MockUserDirectoryProvider::new();
+        let peer_id_b =
+            generate_new_keypair_and_peer_id_from_mnemonic(&mnemonic, None, KeyFormat::Base64Protobuf, None, &provider_b)
+                .unwrap();
+
+        assert_eq!(peer_id_a, peer_id_b);
+    }
+
+    #[test]
+    fn test_generate_new_peer_id_from_invalid_mnemonic_fails() {
+        let provider = MockUserDirectoryProvider::new();
+
+        let result = generate_new_keypair_and_peer_id_from_mnemonic(
+            "not a valid bip39 mnemonic phrase",
+            None,
+            KeyFormat::Base64Protobuf,
+            None,
+            &provider,
+        );
+
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_generate_new_peer_id_with_seed_phrase() {
         let seed_phrase = Some("test_seed_phrase");
         let provider: MockUserDirectoryProvider = MockUserDirectoryProvider::new();
 
         let result = generate_new_keypair_and_peer_id(
-            seed_phrase.as_deref(),
+            seed_phrase,
             &provider,
         );
 
         match result {
             Ok(peer_id) => {
                 // Verify that a PeerId is generated
-                assert!(peer_id.to_base58().len() > 0);
+                assert!(!peer_id.to_base58().is_empty());
                 println!("Generated Peer ID with seed: {}", peer_id);
             }
             Err(e) => panic!("Expected PeerId, got error: {}", e),
@@ -267,7 +554,7 @@ mod tests {
         match result {
             Ok(peer_id) => {
                 // Verify that a PeerId is generated
-                assert!(peer_id.to_base58().len() > 0);
+                assert!(!peer_id.to_base58().is_empty());
                 println!("Generated Peer ID without seed: {}", peer_id);
             }
             Err(e) => panic!("Expected PeerId, got error: {}", e),
@@ -0,0 +1,56 @@
+use std::error::Error;
+use std::path::Path;
+
+/// Restricts `path` (a directory) so only the current user can enter it.
+#[cfg(unix)]
+pub fn restrict_dir_permissions(path: &Path) -> Result<(), Box<dyn Error>> {
+    use std::fs::{set_permissions, Permissions};
+    use std::os::unix::fs::PermissionsExt;
+
+    set_permissions(path, Permissions::from_mode(0o700))?;
+    Ok(())
+}
+
+/// Restricts `path` (a file) so only the current user can read or write it.
+#[cfg(unix)]
+pub fn restrict_file_permissions(path: &Path) -> Result<(), Box<dyn Error>> {
+    use std::fs::{set_permissions, Permissions};
+    use std::os::unix::fs::PermissionsExt;
+
+    set_permissions(path, Permissions::from_mode(0o600))?;
+    Ok(())
+}
+
+/// Windows has no POSIX mode bits; instead, replace the DACL with one
+/// granting full control to the current user only, removing inherited
+/// entries so parent-directory ACLs can't reintroduce broader access.
+#[cfg(windows)]
+pub fn restrict_dir_permissions(path: &Path) -> Result<(), Box<dyn Error>> {
+    restrict_to_current_user(path)
+}
+
+#[cfg(windows)]
+pub fn restrict_file_permissions(path: &Path) -> Result<(), Box<dyn Error>> {
+    restrict_to_current_user(path)
+}
+
+#[cfg(windows)]
+fn restrict_to_current_user(path: &Path) -> Result<(), Box<dyn Error>> {
+    use windows_acl::acl::ACL;
+    use windows_acl::helper::current_user_sid;
+
+    let sid = current_user_sid().ok_or("Could not resolve current user SID")?;
+    let mut acl = ACL::from_file_path(
+        path.to_str().ok_or("Keypair path is not valid UTF-8")?,
+        false,
+    )
+    .map_err(|code| format!("Failed to open ACL (error code {})", code))?;
+
+    // Drop every existing entry, then grant the current user full control,
+    // with inheritance disabled so this entry can't be widened by a parent.
+    acl.clear().map_err(|code| format!("Failed to clear ACL (error code {})", code))?;
+    acl.allow(&sid, true, winapi::um::winnt::GENERIC_ALL)
+        .map_err(|code| format!("Failed to set owner-only ACE (error code {})", code))?;
+
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use hmac::{Hmac, Mac};
+use libp2p::identity::Keypair;
+use sha2::Sha512;
+
+type HmacSha512 = Hmac<Sha512>;
+
+/// Domain label for a libp2p identity branch. Not used by
+/// [`Seed::derive_libp2p_identity`], which takes the master seed bytes as-is.
+pub const LIBP2P_IDENTITY: &[u8] = b"LIBP2P_IDENTITY";
+
+/// Domain label reserved for a future per-chain bridge signing key.
+pub const BRIDGE_SIGNER: &[u8] = b"BRIDGE_SIGNER";
+
+/// A 32-byte master seed from which individual node keys are deterministically
+/// derived. Only the master seed is ever persisted (optionally encrypted, see
+/// [`super::encryption`]); every other key is re-derived from it on load so a
+/// single secret hits the filesystem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Seed([u8; 32]);
+
+impl Seed {
+    pub fn new(bytes: [u8; 32]) -> Self {
+        Seed(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Derives a domain-separated child seed as the first 32 bytes of
+    /// `HMAC-SHA512(key = self, msg = label)`. Chain calls to derive nested
+    /// keys, e.g. `seed.derive(b"NETWORK").derive(b"LIBP2P_IDENTITY")`.
+    pub fn derive(&self, label: &[u8]) -> Seed {
+        let mut mac =
+            HmacSha512::new_from_slice(&self.0).expect("HMAC-SHA512 accepts any key length");
+        mac.update(label);
+        let result = mac.finalize().into_bytes();
+
+        let mut child = [0u8; 32];
+        child.copy_from_slice(&result[..32]);
+        Seed(child)
+    }
+
+    /// Derives this node's libp2p network identity keypair.
+    ///
+    /// Deliberately uses the master seed bytes directly as the ed25519
+    /// secret key, rather than going through [`Seed::derive`] with the
+    /// [`LIBP2P_IDENTITY`] label: the existing `--seed-phrase`/BIP39 paths
+    /// already feed their 32-byte seed straight into the keypair, and
+    /// re-deriving it through an extra HMAC step here would silently change
+    /// the PeerId of every node that already has one. New, non-identity
+    /// keys (e.g. a future [`BRIDGE_SIGNER`]) should go through
+    /// [`Seed::derive`] instead, since they have no prior behavior to
+    /// preserve.
+    pub fn derive_libp2p_identity(&self) -> Keypair {
+        Keypair::ed25519_from_bytes(self.0).expect("seed is always 32 bytes")
+    }
+}
+
+impl super::key_format::EncodableKey for Seed {
+    fn to_raw_bytes(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        Ok(self.as_bytes().to_vec())
+    }
+
+    fn from_raw_bytes(bytes: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        let array: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| "Master seed must be exactly 32 bytes")?;
+        Ok(Seed(array))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_is_deterministic() {
+        let seed = Seed::new([7u8; 32]);
+
+        let a = seed.derive(b"NETWORK");
+        let b = seed.derive(b"NETWORK");
+
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_derive_differs_by_label() {
+        let seed = Seed::new([7u8; 32]);
+
+        let network = seed.derive(b"NETWORK");
+        let bridge_signer = seed.derive(BRIDGE_SIGNER);
+
+        assert_ne!(network, bridge_signer);
+    }
+
+    #[test]
+    fn test_derive_chain_differs_from_single_derive() {
+        let seed = Seed::new([7u8; 32]);
+
+        let chained = seed.derive(b"NETWORK").derive(LIBP2P_IDENTITY);
+        let direct = seed.derive(LIBP2P_IDENTITY);
+
+        assert_ne!(chained, direct);
+    }
+
+    #[test]
+    fn test_derive_libp2p_identity_is_deterministic() {
+        let seed = Seed::new([9u8; 32]);
+
+        let keypair_a = seed.derive_libp2p_identity();
+        let keypair_b = seed.derive_libp2p_identity();
+
+        assert_eq!(keypair_a.public(), keypair_b.public());
+    }
+
+    #[test]
+    fn test_different_seeds_derive_different_identities() {
+        let keypair_a = Seed::new([1u8; 32]).derive_libp2p_identity();
+        let keypair_b = Seed::new([2u8; 32]).derive_libp2p_identity();
+
+        assert_ne!(keypair_a.public(), keypair_b.public());
+    }
+}